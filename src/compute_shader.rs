@@ -0,0 +1,323 @@
+//! Generic compute-to-texture framework.
+//!
+//! [`ComputeShader`] plus [`ComputeShaderPlugin`] let a user register their
+//! own WGSL compute shader and a struct of uniforms/textures -- anything
+//! implementing [`AsBindGroup`] -- and have it dispatched every frame, without
+//! touching any of the fractal-specific code in
+//! [`fractal_system`](crate::fractal_system). Register one
+//! `ComputeShaderPlugin<S>` per shader type.
+
+use bevy::asset::AssetServer;
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_graph::{self, RenderGraph};
+use bevy::render::render_resource::*;
+use bevy::render::renderer::{RenderContext, RenderDevice};
+use bevy::render::texture::FallbackImage;
+use bevy::render::{Extract, RenderApp, RenderStage};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+pub use example::CheckerboardShader;
+
+/// Size in pixels of a single compute workgroup, must match the
+/// `workgroup_size` attribute of registered compute shaders.
+const WORKGROUP_SIZE: u32 = 8;
+
+/// A compute shader that can be registered with [`ComputeShaderPlugin`].
+///
+/// Implementing this on an [`AsBindGroup`] component is all that's needed to
+/// run a custom compute shader: the bind group is whatever uniforms and
+/// (storage) textures the type's `AsBindGroup` implementation binds,
+/// including its output texture, so there is no separate "output image"
+/// concept here -- bind it like any other binding. Storage textures are not
+/// supported by the `AsBindGroup` derive, so a type writing to one implements
+/// `AsBindGroup` by hand, as [`CheckerboardShader`] does.
+pub trait ComputeShader: AsBindGroup + TypeUuid + Component + Clone + Sized {
+    /// The compute shader asset to dispatch.
+    fn shader() -> ShaderRef;
+    /// Entry point within [`shader`](Self::shader) to dispatch.
+    fn entry_point() -> Cow<'static, str>;
+    /// Size in pixels of the texture this shader writes to, used to compute
+    /// the number of workgroups to dispatch.
+    fn size(&self) -> UVec2;
+}
+
+/// Dispatches every [`ComputeShader`] `S` component onto its bound textures
+/// each frame.
+pub struct ComputeShaderPlugin<S>(PhantomData<fn() -> S>);
+
+impl<S> Default for ComputeShaderPlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: ComputeShader> Plugin for ComputeShaderPlugin<S> {
+    fn build(&self, app: &mut App) {
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app
+            .init_resource::<ExtractedComputeShaders<S>>()
+            .init_resource::<ComputeShaderPipeline<S>>()
+            .add_system_to_stage(RenderStage::Extract, extract_compute_shaders::<S>)
+            .add_system_to_stage(RenderStage::Queue, queue_compute_shaders::<S>);
+
+        // Each `S` gets its own node, named after its `TypeUuid` since the
+        // graph needs a unique name per registered shader type.
+        let node_name = format!("compute_shader_{:x}", S::TYPE_UUID.as_u128());
+        let mut render_graph = render_app.world.resource_mut::<RenderGraph>();
+        render_graph.add_node(node_name.clone(), ComputeShaderNode::<S>(PhantomData));
+        render_graph
+            .add_node_edge(node_name, bevy::render::main_graph::node::CAMERA_DRIVER)
+            .unwrap();
+    }
+}
+
+/// Compute pipeline dispatching a single [`ComputeShader`] type `S`.
+#[derive(Resource)]
+struct ComputeShaderPipeline<S: ComputeShader> {
+    pipeline_id: CachedComputePipelineId,
+    bind_group_layout: BindGroupLayout,
+    _marker: PhantomData<fn() -> S>,
+}
+
+impl<S: ComputeShader> FromWorld for ComputeShaderPipeline<S> {
+    fn from_world(world: &mut World) -> Self {
+        let mut system_state: SystemState<(Res<RenderDevice>, Res<AssetServer>)> =
+            SystemState::new(world);
+        let (render_device, asset_server) = system_state.get_mut(world);
+
+        let bind_group_layout = S::bind_group_layout(&render_device);
+
+        let shader = match S::shader() {
+            ShaderRef::Handle(handle) => handle,
+            ShaderRef::Path(path) => asset_server.load(path),
+            ShaderRef::Default => panic!("ComputeShader::shader() must return a shader asset"),
+        };
+
+        let pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_compute_pipeline(ComputePipelineDescriptor {
+                    label: Some(Cow::from(std::any::type_name::<S>())),
+                    layout: Some(vec![bind_group_layout.clone()]),
+                    shader,
+                    shader_defs: Vec::new(),
+                    entry_point: S::entry_point(),
+                });
+
+        Self {
+            pipeline_id,
+            bind_group_layout,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// All `S` components extracted to the render world this frame.
+#[derive(Resource)]
+struct ExtractedComputeShaders<S: ComputeShader> {
+    values: Vec<(Entity, S)>,
+}
+
+impl<S: ComputeShader> Default for ExtractedComputeShaders<S> {
+    fn default() -> Self {
+        Self { values: Vec::new() }
+    }
+}
+
+/// Extract every `S` component to the render world.
+fn extract_compute_shaders<S: ComputeShader>(
+    mut extracted: ResMut<ExtractedComputeShaders<S>>,
+    query: Extract<Query<(Entity, &S)>>,
+) {
+    extracted.values.clear();
+    extracted
+        .values
+        .extend(query.iter().map(|(entity, shader)| (entity, shader.clone())));
+}
+
+/// Bind group and dispatch size for a single `S` instance.
+struct ComputeShaderBindGroup {
+    bind_group: BindGroup,
+    size: UVec2,
+}
+
+/// Bind groups for every extracted `S` instance.
+#[derive(Resource)]
+struct ComputeShaderBindGroups<S: ComputeShader> {
+    values: HashMap<Entity, ComputeShaderBindGroup>,
+    _marker: PhantomData<fn() -> S>,
+}
+
+impl<S: ComputeShader> Default for ComputeShaderBindGroups<S> {
+    fn default() -> Self {
+        Self {
+            values: HashMap::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Queue a bind group for every extracted `S` instance, built from its
+/// `AsBindGroup` implementation.
+fn queue_compute_shaders<S: ComputeShader>(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    gpu_images: Res<RenderAssets<Image>>,
+    fallback_image: Res<FallbackImage>,
+    pipeline: Res<ComputeShaderPipeline<S>>,
+    extracted: Res<ExtractedComputeShaders<S>>,
+) {
+    let mut bind_groups = ComputeShaderBindGroups::<S>::default();
+
+    for (entity, shader) in extracted.values.iter() {
+        let Ok(prepared) = shader.as_bind_group(
+            &pipeline.bind_group_layout,
+            &render_device,
+            &gpu_images,
+            &fallback_image,
+        ) else {
+            continue;
+        };
+
+        bind_groups.values.insert(
+            *entity,
+            ComputeShaderBindGroup {
+                bind_group: prepared.bind_group,
+                size: shader.size(),
+            },
+        );
+    }
+
+    commands.insert_resource(bind_groups);
+}
+
+/// [`render_graph::Node`] which dispatches the compute pipeline for every
+/// extracted `S` instance.
+struct ComputeShaderNode<S>(PhantomData<fn() -> S>);
+
+impl<S: ComputeShader> render_graph::Node for ComputeShaderNode<S> {
+    fn run(
+        &self,
+        _graph: &mut render_graph::RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), render_graph::NodeRunError> {
+        let bind_groups = world.resource::<ComputeShaderBindGroups<S>>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline = world.resource::<ComputeShaderPipeline<S>>();
+
+        // The pipeline may not have finished compiling yet, in which case we
+        // just skip this frame and try again on the next one.
+        let Some(compute_pipeline) = pipeline_cache.get_compute_pipeline(pipeline.pipeline_id)
+        else {
+            return Ok(());
+        };
+
+        let mut pass = render_context
+            .command_encoder
+            .begin_compute_pass(&ComputePassDescriptor::default());
+        pass.set_pipeline(compute_pipeline);
+
+        for bind_group in bind_groups.values.values() {
+            pass.set_bind_group(0, &bind_group.bind_group, &[]);
+
+            let workgroups = (bind_group.size + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+            pass.dispatch_workgroups(workgroups.x, workgroups.y, 1);
+        }
+
+        Ok(())
+    }
+}
+
+/// Reference [`ComputeShader`] implementation proving the generic framework
+/// is reachable on its own, without any of the fractal-specific code in
+/// [`fractal_system`](crate::fractal_system).
+mod example {
+    use super::ComputeShader;
+    use bevy::prelude::*;
+    use bevy::reflect::TypeUuid;
+    use bevy::render::render_asset::RenderAssets;
+    use bevy::render::render_resource::*;
+    use bevy::render::renderer::RenderDevice;
+    use bevy::render::texture::FallbackImage;
+    use std::borrow::Cow;
+
+    /// Fills its bound texture with an 8px checkerboard pattern. `AsBindGroup`
+    /// is implemented by hand here rather than derived, since deriving it
+    /// only supports sampled `texture`/`sampler` bindings, not the storage
+    /// texture a compute shader writes into.
+    #[derive(Component, Clone, TypeUuid)]
+    #[uuid = "c4f184f2-9d36-4f1b-9f63-3e9f3a6d8b2a"]
+    pub struct CheckerboardShader {
+        /// Texture the checkerboard pattern is written into.
+        pub output: Handle<Image>,
+        /// Size in pixels of [`output`](Self::output).
+        pub size: UVec2,
+    }
+
+    impl AsBindGroup for CheckerboardShader {
+        type Data = ();
+
+        fn as_bind_group(
+            &self,
+            layout: &BindGroupLayout,
+            render_device: &RenderDevice,
+            images: &RenderAssets<Image>,
+            _fallback_image: &FallbackImage,
+        ) -> Result<PreparedBindGroup<Self>, AsBindGroupError> {
+            let Some(image) = images.get(&self.output) else {
+                return Err(AsBindGroupError::RetryNextUpdate);
+            };
+
+            let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+                label: Some("checkerboard_shader_bind_group"),
+                layout,
+                entries: &[BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&image.texture_view),
+                }],
+            });
+
+            Ok(PreparedBindGroup {
+                bindings: vec![OwnedBindingResource::TextureView(image.texture_view.clone())],
+                bind_group,
+                data: (),
+            })
+        }
+
+        fn bind_group_layout(render_device: &RenderDevice) -> BindGroupLayout {
+            render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some("checkerboard_shader_bind_group_layout"),
+                entries: &[BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::StorageTexture {
+                        access: StorageTextureAccess::WriteOnly,
+                        format: TextureFormat::Rgba8Unorm,
+                        view_dimension: TextureViewDimension::D2,
+                    },
+                    count: None,
+                }],
+            })
+        }
+    }
+
+    impl ComputeShader for CheckerboardShader {
+        fn shader() -> ShaderRef {
+            "shaders/checkerboard.wgsl".into()
+        }
+
+        fn entry_point() -> Cow<'static, str> {
+            Cow::from("checkerboard")
+        }
+
+        fn size(&self) -> UVec2 {
+            self.size
+        }
+    }
+}
@@ -1,14 +1,29 @@
 //! Bevy fractal system with compute shaders
 
-use bevy::asset::AssetServer;
+use bevy::asset::{AssetLoader, AssetServer, LoadContext, LoadedAsset};
 use bevy::ecs::system::SystemState;
 use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
 use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_graph::{self, RenderGraph};
+use bevy::render::render_resource::encase::UniformBuffer;
 use bevy::render::render_resource::*;
-use bevy::render::renderer::RenderDevice;
+use bevy::render::renderer::{RenderContext, RenderDevice};
 use bevy::render::{Extract, RenderApp, RenderStage};
+use bevy::utils::BoxedFuture;
+use palette::{LinSrgb, Srgb};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Name of the [`ComputeFractalNode`] in the [`RenderGraph`].
+const COMPUTE_FRACTAL_NODE: &str = "compute_fractal";
+
+/// Number of texels in a baked [`FractalGradient`] colormap.
+const MAPPING_SIZE: u32 = 256;
 
 /// Render a fractal using a compute shader.
 #[derive(Component, Clone, Reflect)]
@@ -20,6 +35,80 @@ pub struct ComputeFractalComponent {
     pub iterations: usize,
     /// [`Image`] to which the fractal should be drawn.
     pub output: Handle<Image>,
+    /// Point in the complex plane the view is centered on.
+    pub view_center: Vec2,
+    /// Half-height of the visible region of the complex plane. Decreasing
+    /// this zooms in.
+    pub view_scale: f32,
+    /// Color gradient used to map escape values to a final color.
+    pub gradient: FractalGradient,
+}
+
+impl Default for ComputeFractalComponent {
+    fn default() -> Self {
+        Self {
+            fractal_type: FractalType::Julia(-0.45, 0.55),
+            iterations: 100,
+            output: Default::default(),
+            view_center: Vec2::ZERO,
+            view_scale: 1.5,
+            gradient: FractalGradient::Greyscale,
+        }
+    }
+}
+
+/// A named color gradient used to map a fractal's escape values to colors.
+///
+/// Swapping this out changes the look of a fractal without needing to touch
+/// the compute shader or recompile anything.
+#[derive(Clone, Copy, PartialEq, Reflect)]
+pub enum FractalGradient {
+    /// Black fading to white.
+    Greyscale,
+    /// Deep blue through orange to pale yellow.
+    Fire,
+    /// Deep blue through cyan to white.
+    Ocean,
+}
+
+impl FractalGradient {
+    /// Color stops this gradient interpolates between, from the lowest
+    /// escape value to the highest.
+    fn stops(&self) -> Vec<LinSrgb> {
+        match self {
+            FractalGradient::Greyscale => {
+                vec![LinSrgb::new(0.0, 0.0, 0.0), LinSrgb::new(1.0, 1.0, 1.0)]
+            }
+            FractalGradient::Fire => vec![
+                LinSrgb::new(0.0, 0.0, 0.1),
+                LinSrgb::new(0.9, 0.3, 0.0),
+                LinSrgb::new(1.0, 0.9, 0.4),
+            ],
+            FractalGradient::Ocean => vec![
+                LinSrgb::new(0.0, 0.05, 0.2),
+                LinSrgb::new(0.0, 0.6, 0.8),
+                LinSrgb::new(0.8, 1.0, 1.0),
+            ],
+        }
+    }
+
+    /// Bakes this gradient into [`MAPPING_SIZE`] RGBA8 texels, ready to be
+    /// uploaded as a 1-D colormap texture.
+    fn bake(&self) -> Vec<u8> {
+        let gradient = palette::Gradient::new(self.stops().iter().copied());
+        gradient
+            .take(MAPPING_SIZE as usize)
+            .flat_map(|color: LinSrgb| {
+                let srgb = Srgb::from_linear(color);
+                [
+                    (srgb.red * 255.0) as u8,
+                    (srgb.green * 255.0) as u8,
+                    (srgb.blue * 255.0) as u8,
+                    255,
+                ]
+            })
+            .collect()
+    }
 }
 
 /// Types of fractals which can be generated.
@@ -31,6 +120,43 @@ pub enum FractalType {
     /// This type of fractal has two constants used to calculate the next item
     /// of the set.
     Julia(f64, f64),
+    /// Mandelbrot set fractal.
+    ///
+    /// The inverse of [`Julia`](FractalType::Julia): the per-pixel complex
+    /// coordinate becomes `c`, and `z` starts at zero.
+    Mandelbrot,
+    /// Burning Ship fractal.
+    ///
+    /// Like [`Mandelbrot`](FractalType::Mandelbrot), but `z` is folded into
+    /// the first quadrant, `z = (|Re(z)|, |Im(z)|)`, before each squaring.
+    BurningShip,
+    /// Multibrot set fractal.
+    ///
+    /// Like [`Mandelbrot`](FractalType::Mandelbrot), but `z` is raised to the
+    /// given power instead of being squared.
+    Multibrot(f64),
+}
+
+impl FractalType {
+    /// The constant `c` used to step `z` each iteration of a
+    /// [`Julia`](FractalType::Julia) set. Unused by the other fractal types,
+    /// which instead derive `c` from the pixel coordinate.
+    fn c(&self) -> Vec2 {
+        match self {
+            FractalType::Julia(re, im) => Vec2::new(*re as f32, *im as f32),
+            _ => Vec2::ZERO,
+        }
+    }
+
+    /// The power `z` is raised to each iteration of a
+    /// [`Multibrot`](FractalType::Multibrot) set. Unused by the other
+    /// fractal types, which all square `z`.
+    fn power(&self) -> f32 {
+        match self {
+            FractalType::Multibrot(power) => *power as f32,
+            _ => 2.0,
+        }
+    }
 }
 
 /// Bundle with everything needed to create an entity with a compute fractal
@@ -43,29 +169,266 @@ pub struct ComputeFractalBundle {
     pub sprite: SpriteBundle,
 }
 
+/// Baked colormap texture for a [`ComputeFractalComponent`]'s
+/// [`gradient`](ComputeFractalComponent::gradient), kept up to date by
+/// [`update_fractal_colormaps`].
+#[derive(Component)]
+struct ComputeFractalColormap {
+    gradient: FractalGradient,
+    image: Handle<Image>,
+}
+
+/// Bakes a [`FractalGradient`] into a 1-D [`Image`] whenever it changes, so
+/// the render world always has a colormap texture to sample from.
+fn update_fractal_colormaps(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    query: Query<(
+        Entity,
+        &ComputeFractalComponent,
+        Option<&ComputeFractalColormap>,
+    )>,
+) {
+    for (entity, fractal, colormap) in query.iter() {
+        if let Some(colormap) = colormap {
+            if colormap.gradient == fractal.gradient {
+                continue;
+            }
+        }
+
+        let mut image = Image::new(
+            Extent3d {
+                width: MAPPING_SIZE,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D1,
+            fractal.gradient.bake(),
+            TextureFormat::Rgba8Unorm,
+        );
+        image.texture_descriptor.usage = TextureUsages::COPY_DST
+            | TextureUsages::STORAGE_BINDING
+            | TextureUsages::TEXTURE_BINDING;
+
+        let image = images.add(image);
+        commands.entity(entity).insert(ComputeFractalColormap {
+            gradient: fractal.gradient,
+            image,
+        });
+    }
+}
+
+/// Animates a [`ComputeFractalComponent`]'s Julia constant `c` along a
+/// keyframed path through parameter space.
+///
+/// Because the renderer re-extracts every frame, animating `c` this way
+/// produces a morphing fractal without any changes to the compute shader.
+#[derive(Component, Clone, Reflect)]
+pub struct FractalAnimation {
+    /// Keyframes of the Julia constant `c`, evenly spaced across
+    /// [`duration`](Self::duration).
+    pub path: Vec<Vec2>,
+    /// How long it takes to play through the whole path once.
+    pub duration: Duration,
+    /// Whether to wrap back to the start of the path after reaching the end,
+    /// rather than holding on the last keyframe.
+    pub looping: bool,
+    /// Time elapsed since this animation started, advanced each frame by
+    /// [`animate_fractals`].
+    elapsed: Duration,
+}
+
+impl FractalAnimation {
+    /// Creates an animation from a path of `c` values supplied directly.
+    pub fn new(path: Vec<Vec2>, duration: Duration, looping: bool) -> Self {
+        Self {
+            path,
+            duration,
+            looping,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Synchronously loads an animation from a CSV file of `c` keyframes, one
+    /// `re,im` pair per line. This reads the file directly with blocking I/O
+    /// and does not go through the asset pipeline, so the path won't
+    /// hot-reload; prefer loading a [`FractalKeyframes`] asset with
+    /// [`AssetServer::load`] and [`FractalAnimation::from_keyframes`] when
+    /// that matters.
+    pub fn from_csv(
+        path: impl AsRef<Path>,
+        duration: Duration,
+        looping: bool,
+    ) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let path = parse_keyframes(&contents)?;
+        Ok(Self::new(path, duration, looping))
+    }
+
+    /// Creates an animation from a [`FractalKeyframes`] asset loaded via the
+    /// asset server.
+    pub fn from_keyframes(keyframes: &FractalKeyframes, duration: Duration, looping: bool) -> Self {
+        Self::new(keyframes.path.clone(), duration, looping)
+    }
+
+    /// The interpolated `c` value at the given point in the animation,
+    /// linearly interpolating between adjacent keyframes and wrapping (if
+    /// [`looping`](Self::looping)) or holding the last keyframe otherwise.
+    fn sample(&self, elapsed: Duration) -> Vec2 {
+        match self.path.as_slice() {
+            [] => Vec2::ZERO,
+            [only] => *only,
+            path => {
+                let segments = if self.looping { path.len() } else { path.len() - 1 };
+                let t = elapsed.as_secs_f32() / self.duration.as_secs_f32().max(f32::EPSILON);
+                let t = if self.looping { t.rem_euclid(1.0) } else { t.clamp(0.0, 1.0) };
+
+                let progress = t * segments as f32;
+                let index = (progress as usize).min(segments - 1);
+                let frac = progress - index as f32;
+
+                path[index].lerp(path[(index + 1) % path.len()], frac)
+            }
+        }
+    }
+}
+
+/// Asset holding a keyframe path for a [`FractalAnimation`], loaded from a
+/// CSV file of `c` keyframes (one `re,im` pair per line) by
+/// [`FractalKeyframesLoader`].
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "f4a8f033-8f1e-4a8a-9b3d-2a9e6a7b3b44"]
+pub struct FractalKeyframes {
+    /// Keyframes of the Julia constant `c` parsed from the CSV file.
+    pub path: Vec<Vec2>,
+}
+
+/// [`AssetLoader`] for [`FractalKeyframes`], so keyframe paths can be loaded
+/// (and hot-reloaded) with [`AssetServer::load`] like any other asset.
+#[derive(Default)]
+struct FractalKeyframesLoader;
+
+impl AssetLoader for FractalKeyframesLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let contents = std::str::from_utf8(bytes)?;
+            let path = parse_keyframes(contents)?;
+            load_context.set_default_asset(LoadedAsset::new(FractalKeyframes { path }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["csv"]
+    }
+}
+
+/// Parses a CSV of `c` keyframes, one `re,im` pair per line, shared by
+/// [`FractalKeyframesLoader`] and [`FractalAnimation::from_csv`].
+fn parse_keyframes(contents: &str) -> io::Result<Vec<Vec2>> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let malformed = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("malformed keyframe line, expected `re,im`: {line:?}"),
+                )
+            };
+            let (re, im) = line.split_once(',').ok_or_else(malformed)?;
+            let parse = |s: &str| s.trim().parse().map_err(|_| malformed());
+
+            Ok(Vec2::new(parse(re)?, parse(im)?))
+        })
+        .collect()
+}
+
+/// Advances every [`FractalAnimation`] and writes its interpolated `c` back
+/// into the Julia constant of the paired [`ComputeFractalComponent`].
+fn animate_fractals(
+    time: Res<Time>,
+    mut query: Query<(&mut FractalAnimation, &mut ComputeFractalComponent)>,
+) {
+    for (mut animation, mut fractal) in query.iter_mut() {
+        animation.elapsed += time.delta();
+
+        if !matches!(fractal.fractal_type, FractalType::Julia(..)) {
+            continue;
+        }
+
+        let c = animation.sample(animation.elapsed);
+        fractal.fractal_type = FractalType::Julia(c.x as f64, c.y as f64);
+    }
+}
+
 /// System to render a fractal on its output image.
 pub struct ComputeFractalPlugin;
 
 impl Plugin for ComputeFractalPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<ComputeFractalComponent>();
+        app.register_type::<ComputeFractalComponent>()
+            .register_type::<FractalAnimation>()
+            .add_asset::<FractalKeyframes>()
+            .init_asset_loader::<FractalKeyframesLoader>()
+            .add_system(update_fractal_colormaps)
+            .add_system(animate_fractals);
 
         let render_app = app.sub_app_mut(RenderApp);
         render_app
             .init_resource::<ExtractedFractals>()
+            .init_resource::<ComputeFractalIntermediates>()
             .init_resource::<ComputeFractalPipeline>()
             .add_system_to_stage(RenderStage::Extract, extract_fractals)
             .add_system_to_stage(RenderStage::Queue, queue_fractals);
+
+        let mut render_graph = render_app.world.resource_mut::<RenderGraph>();
+        render_graph.add_node(COMPUTE_FRACTAL_NODE, ComputeFractalNode);
+        render_graph
+            .add_node_edge(
+                COMPUTE_FRACTAL_NODE,
+                bevy::render::main_graph::node::CAMERA_DRIVER,
+            )
+            .unwrap();
     }
 }
 
 /// Compute pipeline for all fractal generation.
 #[derive(Resource)]
 struct ComputeFractalPipeline {
-    /// Compute pipeline for the julia fractal.
+    /// Escape pass pipeline for [`FractalType::Julia`].
     julia_pipeline: CachedComputePipelineId,
-    /// Common bind group for all pipelines.
-    texture_bind_group_layout: BindGroupLayout,
+    /// Escape pass pipeline for [`FractalType::Mandelbrot`].
+    mandelbrot_pipeline: CachedComputePipelineId,
+    /// Escape pass pipeline for [`FractalType::BurningShip`].
+    burning_ship_pipeline: CachedComputePipelineId,
+    /// Escape pass pipeline for [`FractalType::Multibrot`].
+    multibrot_pipeline: CachedComputePipelineId,
+    /// Compute pipeline for the colormap pass shared by all fractal types.
+    colormap_pipeline: CachedComputePipelineId,
+    /// Bind group layout for the escape pass: an `r32float` intermediate
+    /// texture and the [`FractalUniform`]. Shared by every fractal type.
+    escape_bind_group_layout: BindGroupLayout,
+    /// Bind group layout for the colormap pass: the `r32float` intermediate
+    /// texture, the 1-D colormap texture, the final `rgba8unorm` output
+    /// texture, and the [`FractalUniform`].
+    colormap_bind_group_layout: BindGroupLayout,
+}
+
+impl ComputeFractalPipeline {
+    /// The escape pass pipeline matching the given fractal type.
+    fn escape_pipeline(&self, fractal_type: FractalType) -> CachedComputePipelineId {
+        match fractal_type {
+            FractalType::Julia(..) => self.julia_pipeline,
+            FractalType::Mandelbrot => self.mandelbrot_pipeline,
+            FractalType::BurningShip => self.burning_ship_pipeline,
+            FractalType::Multibrot(..) => self.multibrot_pipeline,
+        }
+    }
 }
 
 impl FromWorld for ComputeFractalPipeline {
@@ -74,45 +437,149 @@ impl FromWorld for ComputeFractalPipeline {
             SystemState::new(world);
         let (render_device, asset_server) = system_state.get_mut(world);
 
-        let texture_bind_group_layout =
+        fn uniform_entry(binding: u32) -> BindGroupLayoutEntry {
+            BindGroupLayoutEntry {
+                binding,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: Some(FractalUniform::min_size()),
+                },
+                count: None,
+            }
+        }
+
+        let escape_bind_group_layout =
             render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
-                label: Some("compute_fractal_texture_layout"),
-                entries: &[BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: ShaderStages::COMPUTE,
-                    ty: BindingType::StorageTexture {
-                        access: StorageTextureAccess::ReadWrite,
-                        format: TextureFormat::Rgba8Unorm,
-                        view_dimension: TextureViewDimension::D2,
+                label: Some("compute_fractal_escape_layout"),
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::WriteOnly,
+                            format: TextureFormat::R32Float,
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    uniform_entry(1),
+                ],
+            });
+
+        let colormap_bind_group_layout =
+            render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some("compute_fractal_colormap_layout"),
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::ReadOnly,
+                            format: TextureFormat::R32Float,
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::ReadOnly,
+                            format: TextureFormat::Rgba8Unorm,
+                            view_dimension: TextureViewDimension::D1,
+                        },
+                        count: None,
                     },
-                    count: None,
-                }],
+                    BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::WriteOnly,
+                            format: TextureFormat::Rgba8Unorm,
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    uniform_entry(3),
+                ],
             });
 
         let shader = asset_server.load("shaders/fractal_system.wgsl");
+        let colormap_shader = asset_server.load("shaders/colormap.wgsl");
 
         let mut pipeline_cache = world.resource_mut::<PipelineCache>();
-        let julia_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
-            label: Some(Cow::from("julia_fractal_pipeline")),
-            layout: Some(vec![texture_bind_group_layout.clone()]),
-            shader: shader.clone(),
+
+        let mut queue_escape_pipeline = |label: &'static str, entry_point: &'static str| {
+            pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: Some(Cow::from(label)),
+                layout: Some(vec![escape_bind_group_layout.clone()]),
+                shader: shader.clone(),
+                shader_defs: Vec::new(),
+                entry_point: Cow::from(entry_point),
+            })
+        };
+        let julia_pipeline = queue_escape_pipeline("julia_fractal_pipeline", "julia");
+        let mandelbrot_pipeline =
+            queue_escape_pipeline("mandelbrot_fractal_pipeline", "mandelbrot");
+        let burning_ship_pipeline =
+            queue_escape_pipeline("burning_ship_fractal_pipeline", "burning_ship");
+        let multibrot_pipeline =
+            queue_escape_pipeline("multibrot_fractal_pipeline", "multibrot");
+
+        let colormap_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: Some(Cow::from("fractal_colormap_pipeline")),
+            layout: Some(vec![colormap_bind_group_layout.clone()]),
+            shader: colormap_shader,
             shader_defs: Vec::new(),
-            entry_point: Cow::from("julia"),
+            entry_point: Cow::from("colormap"),
         });
 
         Self {
             julia_pipeline,
-            texture_bind_group_layout,
+            mandelbrot_pipeline,
+            burning_ship_pipeline,
+            multibrot_pipeline,
+            colormap_pipeline,
+            escape_bind_group_layout,
+            colormap_bind_group_layout,
         }
     }
 }
 
+/// Per-fractal parameters uploaded to the GPU as a uniform buffer.
+#[derive(Clone, Copy, ShaderType)]
+struct FractalUniform {
+    /// Constant used when stepping `z` each iteration.
+    c: Vec2,
+    /// Point in the complex plane the view is centered on.
+    view_center: Vec2,
+    /// Half-height of the visible region of the complex plane.
+    view_scale: f32,
+    /// Width divided by height of the output image, so the fractal isn't
+    /// stretched on non-square outputs.
+    view_aspect: f32,
+    /// How many iterations of the fractal set to run.
+    iters: u32,
+    /// Power `z` is raised to each iteration, used by
+    /// [`Multibrot`](FractalType::Multibrot).
+    power: f32,
+}
+
 /// A fractal extracted from the logical ecs world to the render world.
 struct ExtractedFractal {
     entity: Entity,
     fractal_type: FractalType,
     iterations: usize,
     output: Handle<Image>,
+    /// Size in pixels of the [`output`](Self::output) image, used to compute
+    /// the number of workgroups to dispatch.
+    size: UVec2,
+    view_center: Vec2,
+    view_scale: f32,
+    /// Baked 1-D colormap texture used by the colormap pass.
+    colormap: Handle<Image>,
 }
 
 /// All fractals to be processed by the renderer.
@@ -125,24 +592,46 @@ struct ExtractedFractals {
 /// render world.
 fn extract_fractals(
     mut extracted_fractals: ResMut<ExtractedFractals>,
-    query: Extract<Query<(Entity, &ComputeFractalComponent, &ComputedVisibility)>>,
+    images: Extract<Res<Assets<Image>>>,
+    query: Extract<
+        Query<(
+            Entity,
+            &ComputeFractalComponent,
+            &ComputeFractalColormap,
+            &ComputedVisibility,
+        )>,
+    >,
 ) {
     // Clear the extracted fractals from the last frame
     extracted_fractals.fractals.clear();
 
     // Find all visible fractals
-    for (entity, fractal, visibility) in query.iter() {
+    for (entity, fractal, colormap, visibility) in query.iter() {
         // Fractals dont tend to change much, so we dont need to update
         // it whenever it is out of view.
         if !visibility.is_visible() {
             continue;
         }
 
+        // The dispatch node needs to know the output size to compute the
+        // number of workgroups to dispatch.
+        let Some(image) = images.get(&fractal.output) else {
+            continue;
+        };
+        let size = UVec2::new(
+            image.texture_descriptor.size.width,
+            image.texture_descriptor.size.height,
+        );
+
         extracted_fractals.fractals.push(ExtractedFractal {
             entity,
             fractal_type: fractal.fractal_type,
             iterations: fractal.iterations,
             output: fractal.output.clone_weak(),
+            size,
+            view_center: fractal.view_center,
+            view_scale: fractal.view_scale,
+            colormap: colormap.image.clone_weak(),
         });
     }
 }
@@ -155,31 +644,307 @@ fn queue_fractals(
     gpu_images: Res<RenderAssets<Image>>,
     pipeline: Res<ComputeFractalPipeline>,
     extracted_fractals: Res<ExtractedFractals>,
+    mut intermediates: ResMut<ComputeFractalIntermediates>,
 ) {
     let mut bind_groups = ComputeFractalBindGroups::default();
 
+    // Drop intermediate textures for entities that are no longer extracted
+    // (despawned or gone invisible), otherwise they leak for the process
+    // lifetime.
+    let live_entities: HashSet<Entity> = extracted_fractals
+        .fractals
+        .iter()
+        .map(|fractal| fractal.entity)
+        .collect();
+    intermediates
+        .values
+        .retain(|entity, _| live_entities.contains(entity));
+
     for fractal in extracted_fractals.fractals.iter() {
-        // Get a texture view of the fractal output image
-        let view = &gpu_images[&fractal.output];
+        let Some(output_view) = gpu_images.get(&fractal.output) else {
+            continue;
+        };
+        let Some(colormap_view) = gpu_images.get(&fractal.colormap) else {
+            continue;
+        };
+
+        // The escape pass writes its raw scalar result here; (re)create it
+        // whenever the output image is resized.
+        let escape_texture = intermediates
+            .values
+            .entry(fractal.entity)
+            .and_modify(|entry| {
+                if entry.0 != fractal.size {
+                    *entry = create_escape_texture(&render_device, fractal.size);
+                }
+            })
+            .or_insert_with(|| create_escape_texture(&render_device, fractal.size))
+            .1
+            .clone();
 
-        // Create a compatible bind group with the texture view
-        let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+        // Pack this fractal's parameters into a uniform buffer for the shader
+        let uniform = FractalUniform {
+            c: fractal.fractal_type.c(),
+            view_center: fractal.view_center,
+            view_scale: fractal.view_scale,
+            view_aspect: fractal.size.x as f32 / fractal.size.y as f32,
+            iters: fractal.iterations as u32,
+            power: fractal.fractal_type.power(),
+        };
+        let mut uniform_buffer = UniformBuffer::new(Vec::new());
+        uniform_buffer.write(&uniform).unwrap();
+        let uniform_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
             label: None,
-            layout: &pipeline.texture_bind_group_layout,
-            entries: &[BindGroupEntry {
-                binding: 0,
-                resource: BindingResource::TextureView(&view.texture_view),
-            }],
+            contents: &uniform_buffer.into_inner(),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
         });
 
-        bind_groups.values.insert(fractal.entity, bind_group);
+        let escape_bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.escape_bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&escape_texture),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let colormap_bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.colormap_bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&escape_texture),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(&colormap_view.texture_view),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::TextureView(&output_view.texture_view),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        bind_groups.values.insert(
+            fractal.entity,
+            FractalBindGroups {
+                escape: escape_bind_group,
+                colormap: colormap_bind_group,
+            },
+        );
     }
 
     commands.insert_resource(bind_groups);
 }
 
+/// Creates the `r32float` intermediate texture the escape pass writes into.
+fn create_escape_texture(render_device: &RenderDevice, size: UVec2) -> (UVec2, TextureView) {
+    let texture = render_device.create_texture(&TextureDescriptor {
+        label: Some("compute_fractal_escape_texture"),
+        size: Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::R32Float,
+        usage: TextureUsages::STORAGE_BINDING,
+    });
+
+    (size, texture.create_view(&TextureViewDescriptor::default()))
+}
+
+/// Per-entity `r32float` intermediate textures written by the escape pass and
+/// read by the colormap pass, kept alongside the size they were created at
+/// so they can be recreated on resize.
+#[derive(Default, Resource)]
+struct ComputeFractalIntermediates {
+    values: HashMap<Entity, (UVec2, TextureView)>,
+}
+
+/// Bind groups for a single fractal's escape and colormap passes.
+struct FractalBindGroups {
+    escape: BindGroup,
+    colormap: BindGroup,
+}
+
 /// Bind groups for all extracted fractals.
 #[derive(Default, Resource)]
 struct ComputeFractalBindGroups {
-    values: HashMap<Entity, BindGroup>,
+    values: HashMap<Entity, FractalBindGroups>,
+}
+
+/// Size in pixels of a single compute workgroup, must match the
+/// `workgroup_size` attribute of the compute shaders.
+const WORKGROUP_SIZE: u32 = 8;
+
+/// [`render_graph::Node`] which dispatches the compute pipeline for every
+/// extracted fractal, writing the result into its output texture.
+///
+/// Runs before [`CAMERA_DRIVER`](bevy::render::main_graph::node::CAMERA_DRIVER)
+/// so the sprite displaying the output image is drawn with up to date data.
+struct ComputeFractalNode;
+
+impl render_graph::Node for ComputeFractalNode {
+    fn run(
+        &self,
+        _graph: &mut render_graph::RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), render_graph::NodeRunError> {
+        let bind_groups = world.resource::<ComputeFractalBindGroups>();
+        let extracted_fractals = world.resource::<ExtractedFractals>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline = world.resource::<ComputeFractalPipeline>();
+
+        // The colormap pipeline may not have finished compiling yet, in which
+        // case we just skip this frame and try again on the next one.
+        let Some(colormap_pipeline) =
+            pipeline_cache.get_compute_pipeline(pipeline.colormap_pipeline)
+        else {
+            return Ok(());
+        };
+
+        // First pass: write each fractal's raw escape value into its
+        // intermediate texture.
+        {
+            let mut pass = render_context
+                .command_encoder
+                .begin_compute_pass(&ComputePassDescriptor::default());
+
+            for fractal in extracted_fractals.fractals.iter() {
+                let Some(bind_group) = bind_groups.values.get(&fractal.entity) else {
+                    continue;
+                };
+
+                // The escape pipeline for this fractal's type may not have
+                // finished compiling yet; skip it for this frame if so.
+                let escape_pipeline_id = pipeline.escape_pipeline(fractal.fractal_type);
+                let Some(compute_pipeline) = pipeline_cache.get_compute_pipeline(escape_pipeline_id)
+                else {
+                    continue;
+                };
+                pass.set_pipeline(compute_pipeline);
+                pass.set_bind_group(0, &bind_group.escape, &[]);
+
+                let workgroups = (fractal.size + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+                pass.dispatch_workgroups(workgroups.x, workgroups.y, 1);
+            }
+        }
+
+        // Second pass: map each fractal's escape value through its colormap
+        // into the final output texture.
+        {
+            let mut pass = render_context
+                .command_encoder
+                .begin_compute_pass(&ComputePassDescriptor::default());
+
+            pass.set_pipeline(colormap_pipeline);
+            for fractal in extracted_fractals.fractals.iter() {
+                let Some(bind_group) = bind_groups.values.get(&fractal.entity) else {
+                    continue;
+                };
+
+                pass.set_bind_group(0, &bind_group.colormap, &[]);
+
+                let workgroups = (fractal.size + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+                pass.dispatch_workgroups(workgroups.x, workgroups.y, 1);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_empty_path_is_zero() {
+        let animation = FractalAnimation::new(Vec::new(), Duration::from_secs(1), false);
+        assert_eq!(animation.sample(Duration::from_millis(500)), Vec2::ZERO);
+    }
+
+    #[test]
+    fn sample_single_keyframe_is_constant() {
+        let keyframe = Vec2::new(0.3, -0.2);
+        let animation = FractalAnimation::new(vec![keyframe], Duration::from_secs(1), true);
+        assert_eq!(animation.sample(Duration::ZERO), keyframe);
+        assert_eq!(animation.sample(Duration::from_secs(10)), keyframe);
+    }
+
+    #[test]
+    fn sample_zero_duration_holds_last_keyframe() {
+        let path = vec![Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0)];
+        let animation = FractalAnimation::new(path, Duration::ZERO, false);
+        assert_eq!(animation.sample(Duration::from_millis(1)), Vec2::new(1.0, 1.0));
+    }
+
+    #[test]
+    fn sample_interpolates_linearly_between_keyframes() {
+        let path = vec![Vec2::new(0.0, 0.0), Vec2::new(2.0, 0.0)];
+        let animation = FractalAnimation::new(path, Duration::from_secs(2), false);
+        assert_eq!(animation.sample(Duration::from_secs(1)), Vec2::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn sample_holds_last_keyframe_when_not_looping() {
+        let path = vec![Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0)];
+        let animation = FractalAnimation::new(path, Duration::from_secs(1), false);
+        assert_eq!(animation.sample(Duration::from_secs(5)), Vec2::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn sample_wraps_when_looping() {
+        let path = vec![Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(2.0, 0.0)];
+        let animation = FractalAnimation::new(path, Duration::from_secs(3), true);
+        // One full period past the start should land back at the first keyframe.
+        assert_eq!(animation.sample(Duration::from_secs(3)), Vec2::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn parse_keyframes_reads_re_im_pairs() {
+        let path = parse_keyframes("-0.45,0.55\n0.1, -0.2\n").unwrap();
+        assert_eq!(path, vec![Vec2::new(-0.45, 0.55), Vec2::new(0.1, -0.2)]);
+    }
+
+    #[test]
+    fn parse_keyframes_skips_blank_lines() {
+        let path = parse_keyframes("0.0,0.0\n\n1.0,1.0\n").unwrap();
+        assert_eq!(path, vec![Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0)]);
+    }
+
+    #[test]
+    fn parse_keyframes_empty_file_is_empty_path() {
+        let path = parse_keyframes("").unwrap();
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn parse_keyframes_rejects_malformed_line() {
+        let result = parse_keyframes("not_a_pair\n");
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn parse_keyframes_rejects_non_numeric_component() {
+        let result = parse_keyframes("abc,0.5\n");
+        assert!(result.is_err());
+    }
 }
@@ -2,19 +2,26 @@
 
 #![warn(missing_docs)]
 
+mod compute_shader;
 mod fractal_system;
 
+use compute_shader::{CheckerboardShader, ComputeShaderPlugin};
 use fractal_system::{
-    compute_fractal_system, ComputeFractalBundle, ComputeFractalComponent, FractalType,
+    ComputeFractalBundle, ComputeFractalComponent, ComputeFractalPlugin, FractalAnimation,
+    FractalType,
 };
 
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
 use bevy::window::{PresentMode, WindowDescriptor};
+use std::time::Duration;
 
 /// The resolution of the output image for the fractal.
 const OUTPUT_RESOLUTION: UVec2 = UVec2 { x: 1280, y: 720 };
 
+/// Path to the CSV keyframe path animating the Julia constant `c`.
+const JULIA_ANIMATION_PATH: &str = "assets/animations/julia_c.csv";
+
 fn main() {
     App::new()
         .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
@@ -26,9 +33,11 @@ fn main() {
             },
             ..Default::default()
         }))
+        .add_plugin(ComputeFractalPlugin)
+        .add_plugin(ComputeShaderPlugin::<CheckerboardShader>::default())
         .add_startup_system(prepare_camera)
         .add_startup_system(prepare_fractal)
-        .add_system(compute_fractal_system)
+        .add_startup_system(prepare_checkerboard)
         .run();
 }
 
@@ -58,20 +67,65 @@ fn prepare_fractal(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
     // Save the image as an asset and get a handle
     let image = images.add(image);
 
-    // Spawn a sprite to render the fractal
-    commands.spawn(ComputeFractalBundle {
-        compute_fractal: ComputeFractalComponent {
-            fractal_type: FractalType::Julia(-0.45, 0.55),
-            iterations: 100,
-            output: image.clone(),
+    let animation = FractalAnimation::from_csv(JULIA_ANIMATION_PATH, Duration::from_secs(8), true)
+        .expect("failed to load Julia animation keyframes");
+
+    // Spawn a sprite to render the fractal, morphing through the keyframed
+    // `c` path loaded above.
+    commands
+        .spawn(ComputeFractalBundle {
+            compute_fractal: ComputeFractalComponent {
+                fractal_type: FractalType::Julia(-0.45, 0.55),
+                iterations: 100,
+                output: image.clone(),
+                ..Default::default()
+            },
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(OUTPUT_RESOLUTION.as_vec2()),
+                    ..default()
+                },
+                texture: image.clone(),
+                ..Default::default()
+            },
+        })
+        .insert(animation);
+}
+
+/// Size in pixels of the [`CheckerboardShader`] demo output.
+const CHECKERBOARD_RESOLUTION: UVec2 = UVec2 { x: 256, y: 256 };
+
+/// Spawn a [`CheckerboardShader`]-backed sprite, demonstrating the generic
+/// [`compute_shader`] framework independently of the fractal-specific code.
+fn prepare_checkerboard(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: CHECKERBOARD_RESOLUTION.x,
+            height: CHECKERBOARD_RESOLUTION.y,
+            depth_or_array_layers: 1,
         },
-        sprite: SpriteBundle {
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8Unorm,
+    );
+
+    image.texture_descriptor.usage =
+        TextureUsages::COPY_DST | TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING;
+
+    let image = images.add(image);
+
+    commands
+        .spawn(CheckerboardShader {
+            output: image.clone(),
+            size: CHECKERBOARD_RESOLUTION,
+        })
+        .insert(SpriteBundle {
             sprite: Sprite {
-                custom_size: Some(OUTPUT_RESOLUTION.as_vec2()),
+                custom_size: Some(CHECKERBOARD_RESOLUTION.as_vec2()),
                 ..default()
             },
-            texture: image.clone(),
+            texture: image,
+            transform: Transform::from_xyz(-720.0, 0.0, 0.0),
             ..Default::default()
-        },
-    });
+        });
 }